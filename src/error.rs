@@ -0,0 +1,83 @@
+use std::error::Error;
+use std::fmt;
+
+/// Returned when adding a `Record` whose canonical `prefix` or `uri_prefix`
+/// already exists in the `Converter` (as another record's prefix, URI prefix,
+/// or synonym). The new record's own synonyms are not checked
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateRecordError(pub String);
+
+impl fmt::Display for DuplicateRecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "duplicate record for: {}", self.0)
+    }
+}
+
+impl Error for DuplicateRecordError {}
+
+/// Returned by `Converter::add_record` when a `Record` cannot be added:
+/// either its prefix/URI prefix collides with an existing record, or its
+/// `pattern` is not a valid regular expression
+#[derive(Debug)]
+pub enum RecordError {
+    /// The record's prefix or URI prefix already exists in the `Converter`
+    Duplicate(DuplicateRecordError),
+    /// The record's `pattern` failed to compile as a regular expression
+    Pattern(regex::Error),
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecordError::Duplicate(err) => write!(f, "{}", err),
+            RecordError::Pattern(err) => write!(f, "invalid pattern: {}", err),
+        }
+    }
+}
+
+impl Error for RecordError {}
+
+impl From<DuplicateRecordError> for RecordError {
+    fn from(err: DuplicateRecordError) -> Self {
+        RecordError::Duplicate(err)
+    }
+}
+
+impl From<regex::Error> for RecordError {
+    fn from(err: regex::Error) -> Self {
+        RecordError::Pattern(err)
+    }
+}
+
+/// Returned when loading a `Converter` from a serialized prefix map, extended
+/// prefix map, or JSON-LD context
+#[derive(Debug)]
+pub enum LoaderError {
+    /// The input was not valid JSON, or did not match the expected shape
+    Parse(serde_json::Error),
+    /// A record parsed from the input could not be added to the `Converter`
+    Record(RecordError),
+}
+
+impl fmt::Display for LoaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoaderError::Parse(err) => write!(f, "failed to parse input: {}", err),
+            LoaderError::Record(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for LoaderError {}
+
+impl From<serde_json::Error> for LoaderError {
+    fn from(err: serde_json::Error) -> Self {
+        LoaderError::Parse(err)
+    }
+}
+
+impl From<RecordError> for LoaderError {
+    fn from(err: RecordError) -> Self {
+        LoaderError::Record(err)
+    }
+}
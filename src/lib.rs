@@ -1,10 +1,38 @@
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
 use trie_rs::{Trie, TrieBuilder};
 
-use crate::error::DuplicateRecordError;
+use crate::error::{DuplicateRecordError, LoaderError, RecordError};
 pub mod error;
 
+/// Stores the prefix and local unique identifier for a compact URI (CURIE),
+/// decomposed so callers don't have to re-parse a CURIE string themselves
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reference {
+    pub prefix: String,
+    pub identifier: String,
+}
+
+/// A prefix/identifier pair as a plain tuple, for callers that don't need
+/// `Reference`'s named fields
+pub type ReferenceTuple = (String, String);
+
+impl Reference {
+    /// Convert this `Reference` into a `ReferenceTuple`
+    pub fn as_tuple(&self) -> ReferenceTuple {
+        (self.prefix.clone(), self.identifier.clone())
+    }
+}
+
+impl From<Reference> for ReferenceTuple {
+    fn from(reference: Reference) -> Self {
+        (reference.prefix, reference.identifier)
+    }
+}
+
 /// A CURIE `Record`, containing its prefixes and URI prefixes
 #[derive(Debug, Clone)]
 pub struct Record {
@@ -12,7 +40,34 @@ pub struct Record {
     uri_prefix: String,
     prefix_synonyms: HashSet<String>,
     uri_prefix_synonyms: HashSet<String>,
-    // TODO: pattern: Option<String>,
+    pattern: Option<String>,
+}
+
+/// The shape of a single entry in an extended prefix map, as parsed by
+/// `Converter::from_extended_prefix_map`
+#[derive(Deserialize)]
+struct ExtendedPrefixMapRecord {
+    prefix: String,
+    uri_prefix: String,
+    #[serde(default)]
+    prefix_synonyms: HashSet<String>,
+    #[serde(default)]
+    uri_prefix_synonyms: HashSet<String>,
+    #[serde(default)]
+    pattern: Option<String>,
+}
+
+/// The shape of a single entry written out by
+/// `Converter::write_extended_prefix_map`, mirroring `ExtendedPrefixMapRecord`
+/// but with synonyms sorted for reproducible output
+#[derive(Serialize)]
+struct ExtendedPrefixMapEntry<'a> {
+    prefix: &'a str,
+    uri_prefix: &'a str,
+    prefix_synonyms: Vec<&'a str>,
+    uri_prefix_synonyms: Vec<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pattern: Option<&'a str>,
 }
 
 /// A `Converter` is composed of 2 HashMaps (one for prefixes, one for URIs),
@@ -20,9 +75,9 @@ pub struct Record {
 pub struct Converter {
     prefix_map: HashMap<String, Arc<Record>>,
     uri_map: HashMap<String, Arc<Record>>,
+    pattern_map: HashMap<String, Regex>,
     trie_builder: TrieBuilder<u8>,
     trie: Trie<u8>,
-    // TODO: pattern_map: HashMap<String, String>
 }
 
 impl Converter {
@@ -31,23 +86,206 @@ impl Converter {
         Converter {
             prefix_map: HashMap::new(),
             uri_map: HashMap::new(),
+            pattern_map: HashMap::new(),
             trie_builder: TrieBuilder::new(),
             trie: TrieBuilder::new().build(),
         }
     }
 
+    /// Build a `Converter` from a simple prefix map: a JSON object mapping
+    /// each prefix directly to its URI prefix, e.g. `{"doid": "http://purl.obolibrary.org/obo/DOID_"}`.
+    /// Records built this way have no synonyms.
+    pub fn from_prefix_map(data: &str) -> Result<Converter, LoaderError> {
+        let prefix_map: HashMap<String, String> = serde_json::from_str(data)?;
+        let records = prefix_map.into_iter().map(|(prefix, uri_prefix)| Record {
+            prefix,
+            uri_prefix,
+            prefix_synonyms: HashSet::new(),
+            uri_prefix_synonyms: HashSet::new(),
+            pattern: None,
+        });
+        let mut converter = Converter::new();
+        converter.add_records(records)?;
+        Ok(converter)
+    }
+
+    /// Build a `Converter` from an extended prefix map: a JSON array of
+    /// objects with `prefix`, `uri_prefix`, `prefix_synonyms`, and
+    /// `uri_prefix_synonyms` keys, mapping directly onto `Record`'s fields
+    pub fn from_extended_prefix_map(data: &str) -> Result<Converter, LoaderError> {
+        let records: Vec<ExtendedPrefixMapRecord> = serde_json::from_str(data)?;
+        let records = records.into_iter().map(|record| Record {
+            prefix: record.prefix,
+            uri_prefix: record.uri_prefix,
+            prefix_synonyms: record.prefix_synonyms,
+            uri_prefix_synonyms: record.uri_prefix_synonyms,
+            pattern: record.pattern,
+        });
+        let mut converter = Converter::new();
+        converter.add_records(records)?;
+        Ok(converter)
+    }
+
+    /// Build a `Converter` from a JSON-LD document's `@context`. Only
+    /// string-valued entries are treated as prefix/URI prefix pairs; `@`-prefixed
+    /// keyword keys and non-string values (e.g. nested `@id`/`@type` objects)
+    /// are skipped
+    pub fn from_jsonld_context(data: &str) -> Result<Converter, LoaderError> {
+        let document: serde_json::Value = serde_json::from_str(data)?;
+        let context = document
+            .get("@context")
+            .and_then(|context| context.as_object())
+            .cloned()
+            .unwrap_or_default();
+
+        // `write_jsonld_context` emits one context entry per prefix synonym,
+        // all pointing at the same `uri_prefix`; fold those back into a
+        // single record's `prefix_synonyms` instead of producing several
+        // records that collide on `uri_prefix` in `add_records`
+        let mut records: HashMap<String, Record> = HashMap::new();
+        for (prefix, uri_prefix) in context {
+            if prefix.starts_with('@') {
+                continue;
+            }
+            let uri_prefix = match uri_prefix.as_str() {
+                Some(uri_prefix) => uri_prefix.to_string(),
+                None => continue,
+            };
+            match records.get_mut(&uri_prefix) {
+                Some(record) => {
+                    record.prefix_synonyms.insert(prefix);
+                }
+                None => {
+                    records.insert(
+                        uri_prefix.clone(),
+                        Record {
+                            prefix,
+                            uri_prefix,
+                            prefix_synonyms: HashSet::new(),
+                            uri_prefix_synonyms: HashSet::new(),
+                            pattern: None,
+                        },
+                    );
+                }
+            }
+        }
+        let mut converter = Converter::new();
+        converter.add_records(records.into_values())?;
+        Ok(converter)
+    }
+
+    /// Serialize this `Converter` to an extended prefix map: a JSON array of
+    /// objects with `prefix`, `uri_prefix`, `prefix_synonyms`, and
+    /// `uri_prefix_synonyms` keys. Records and synonyms are sorted so the
+    /// output is reproducible despite `HashSet`/`HashMap` iteration order
+    pub fn write_extended_prefix_map(&self) -> Result<String, serde_json::Error> {
+        let mut records = self.distinct_records();
+        records.sort_by(|a, b| a.prefix.cmp(&b.prefix));
+
+        let entries: Vec<ExtendedPrefixMapEntry> = records
+            .iter()
+            .map(|record| {
+                let mut prefix_synonyms: Vec<&str> =
+                    record.prefix_synonyms.iter().map(String::as_str).collect();
+                prefix_synonyms.sort_unstable();
+                let mut uri_prefix_synonyms: Vec<&str> = record
+                    .uri_prefix_synonyms
+                    .iter()
+                    .map(String::as_str)
+                    .collect();
+                uri_prefix_synonyms.sort_unstable();
+
+                ExtendedPrefixMapEntry {
+                    prefix: &record.prefix,
+                    uri_prefix: &record.uri_prefix,
+                    prefix_synonyms,
+                    uri_prefix_synonyms,
+                    pattern: record.pattern.as_deref(),
+                }
+            })
+            .collect();
+
+        serde_json::to_string_pretty(&entries)
+    }
+
+    /// Serialize this `Converter` to a JSON-LD `@context`, including every
+    /// prefix synonym as its own context entry pointing at the same URI prefix
+    pub fn write_jsonld_context(&self) -> Result<String, serde_json::Error> {
+        let mut records = self.distinct_records();
+        records.sort_by(|a, b| a.prefix.cmp(&b.prefix));
+
+        let mut context = serde_json::Map::new();
+        for record in &records {
+            context.insert(
+                record.prefix.clone(),
+                serde_json::Value::String(record.uri_prefix.clone()),
+            );
+            let mut synonyms: Vec<&String> = record.prefix_synonyms.iter().collect();
+            synonyms.sort();
+            for synonym in synonyms {
+                context.insert(
+                    synonym.clone(),
+                    serde_json::Value::String(record.uri_prefix.clone()),
+                );
+            }
+        }
+
+        let document = serde_json::json!({ "@context": context });
+        serde_json::to_string_pretty(&document)
+    }
+
+    /// Serialize this `Converter`'s canonical records to a two-column
+    /// `prefix\turi_prefix` TSV table
+    pub fn write_tsv(&self) -> String {
+        let mut records = self.distinct_records();
+        records.sort_by(|a, b| a.prefix.cmp(&b.prefix));
+
+        let mut tsv = String::from("prefix\turi_prefix\n");
+        for record in records {
+            tsv.push_str(&format!("{}\t{}\n", record.prefix, record.uri_prefix));
+        }
+        tsv
+    }
+
     /// When adding a new CURIE we create a reference to the `Record` (Arc)
     /// And we use this reference in the prefix and URI hashmaps
-    pub fn add_record(&mut self, record: Record) -> Result<(), DuplicateRecordError> {
-        let rec = Arc::new(record);
-        if self.prefix_map.contains_key(&rec.prefix) {
-            return Err(DuplicateRecordError(rec.prefix.clone()));
+    pub fn add_record(&mut self, record: Record) -> Result<(), RecordError> {
+        self.insert_record(record)?;
+        self.trie = self.trie_builder.build();
+        Ok(())
+    }
+
+    /// Add many `Record`s at once, rebuilding the trie only once at the end
+    /// instead of after every insertion — much faster than calling
+    /// `add_record` in a loop when loading a large vocabulary
+    pub fn add_records(
+        &mut self,
+        records: impl IntoIterator<Item = Record>,
+    ) -> Result<(), RecordError> {
+        for record in records {
+            self.insert_record(record)?;
+        }
+        self.trie = self.trie_builder.build();
+        Ok(())
+    }
+
+    /// Validate and insert `record` into the prefix/URI/pattern maps and push
+    /// its URI prefixes onto the trie builder. Does not rebuild the trie;
+    /// callers must do so afterward
+    fn insert_record(&mut self, record: Record) -> Result<(), RecordError> {
+        if self.prefix_map.contains_key(&record.prefix) {
+            return Err(DuplicateRecordError(record.prefix.clone()).into());
         }
-        if self.uri_map.contains_key(&rec.uri_prefix) {
-            return Err(DuplicateRecordError(rec.uri_prefix.clone()));
+        if self.uri_map.contains_key(&record.uri_prefix) {
+            return Err(DuplicateRecordError(record.uri_prefix.clone()).into());
         }
         // TODO: check if synonyms are unique?
+        let pattern = match &record.pattern {
+            Some(pattern) => Some(Regex::new(pattern)?),
+            None => None,
+        };
 
+        let rec = Arc::new(record);
         self.prefix_map.insert(rec.prefix.clone(), rec.clone());
         self.uri_map.insert(rec.uri_prefix.clone(), rec.clone());
         self.trie_builder.push(&rec.uri_prefix);
@@ -58,17 +296,305 @@ impl Converter {
             self.uri_map.insert(uri_prefix.clone(), rec.clone());
             self.trie_builder.push(uri_prefix);
         }
+        if let Some(pattern) = pattern {
+            self.pattern_map.insert(rec.prefix.clone(), pattern);
+        }
+        Ok(())
+    }
+
+    /// Register a synonym-free `Record` for `prefix`/`uri_prefix` in one call,
+    /// without having to build a full `Record` by hand
+    pub fn add_prefix(
+        &mut self,
+        prefix: &str,
+        uri_prefix: &str,
+    ) -> Result<(), DuplicateRecordError> {
+        let record = Record {
+            prefix: prefix.to_string(),
+            uri_prefix: uri_prefix.to_string(),
+            prefix_synonyms: HashSet::new(),
+            uri_prefix_synonyms: HashSet::new(),
+            pattern: None,
+        };
+        self.insert_record(record).map_err(|err| match err {
+            RecordError::Duplicate(err) => err,
+            RecordError::Pattern(_) => unreachable!("add_prefix never sets a pattern"),
+        })?;
         self.trie = self.trie_builder.build();
         Ok(())
     }
 
-    // TODO: fn add_curie()
+    /// Register `prefix`/`uri_prefix` via `add_prefix`, then expand
+    /// `sample_curie` to verify the new record behaves as expected. Returns
+    /// `None` if `sample_curie` does not expand (e.g. it uses a different
+    /// prefix than the one just registered)
+    pub fn add_curie(
+        &mut self,
+        prefix: &str,
+        uri_prefix: &str,
+        sample_curie: &str,
+    ) -> Result<Option<String>, DuplicateRecordError> {
+        self.add_prefix(prefix, uri_prefix)?;
+        match self.parse_curie(sample_curie) {
+            Some(reference) if reference.prefix == prefix => Ok(self.expand_reference(&reference)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Merge several `Converter`s into one, in priority order.
+    ///
+    /// When two input records share the same canonical `prefix` or
+    /// `uri_prefix`, the record coming from the earlier `Converter` wins as
+    /// the primary record, and the later one's prefix/URI prefix (along with
+    /// all of its synonyms) are folded into the winner's synonym sets rather
+    /// than raising a `DuplicateRecordError`. Set `case_insensitive` to match
+    /// prefixes regardless of case while merging.
+    pub fn chain(converters: Vec<Converter>, case_insensitive: bool) -> Converter {
+        let mut merged = Converter::new();
+        for converter in converters {
+            for record in converter.distinct_records() {
+                merged.merge_record(&record, case_insensitive);
+            }
+        }
+        merged.trie = merged.trie_builder.build();
+        merged
+    }
+
+    /// Enumerate the distinct canonical `Record`s held by this `Converter`,
+    /// collapsing the aliases in `prefix_map`/`uri_map` that point at the
+    /// same underlying `Arc`
+    fn distinct_records(&self) -> Vec<Arc<Record>> {
+        let mut records: Vec<Arc<Record>> = Vec::new();
+        for rec in self.prefix_map.values() {
+            if !records.iter().any(|r| Arc::ptr_eq(r, rec)) {
+                records.push(rec.clone());
+            }
+        }
+        records
+    }
+
+    /// Find the existing `Record`, if any, that `prefix` or `uri_prefix`
+    /// already resolves to
+    fn find_existing(
+        &self,
+        prefix: &str,
+        uri_prefix: &str,
+        case_insensitive: bool,
+    ) -> Option<Arc<Record>> {
+        if case_insensitive {
+            let prefix = prefix.to_lowercase();
+            self.prefix_map
+                .iter()
+                .find(|(key, _)| key.to_lowercase() == prefix)
+                .map(|(_, rec)| rec.clone())
+                .or_else(|| self.uri_map.get(uri_prefix).cloned())
+        } else {
+            self.prefix_map
+                .get(prefix)
+                .cloned()
+                .or_else(|| self.uri_map.get(uri_prefix).cloned())
+        }
+    }
+
+    /// Fold `incoming` into `self`: if its canonical prefix or URI prefix
+    /// already resolves to an existing record, absorb `incoming` (and its
+    /// synonyms) into that record's synonym sets; otherwise insert it as a
+    /// new record. Does not rebuild the trie; callers that insert in bulk
+    /// should rebuild it once afterward.
+    fn merge_record(&mut self, incoming: &Record, case_insensitive: bool) {
+        match self.find_existing(&incoming.prefix, &incoming.uri_prefix, case_insensitive) {
+            Some(existing) => {
+                let mut prefix_synonyms = existing.prefix_synonyms.clone();
+                let mut uri_prefix_synonyms = existing.uri_prefix_synonyms.clone();
+
+                absorb(&incoming.prefix, &existing.prefix, &mut prefix_synonyms);
+                for synonym in &incoming.prefix_synonyms {
+                    absorb(synonym, &existing.prefix, &mut prefix_synonyms);
+                }
+                absorb(
+                    &incoming.uri_prefix,
+                    &existing.uri_prefix,
+                    &mut uri_prefix_synonyms,
+                );
+                for synonym in &incoming.uri_prefix_synonyms {
+                    absorb(synonym, &existing.uri_prefix, &mut uri_prefix_synonyms);
+                }
+
+                // Prefer the existing record's pattern, but fall back to the
+                // incoming one instead of silently dropping it when `existing`
+                // doesn't have a pattern of its own
+                let pattern = existing.pattern.clone().or_else(|| incoming.pattern.clone());
+
+                let merged = Arc::new(Record {
+                    prefix: existing.prefix.clone(),
+                    uri_prefix: existing.uri_prefix.clone(),
+                    prefix_synonyms,
+                    uri_prefix_synonyms,
+                    pattern,
+                });
+
+                for value in self.prefix_map.values_mut() {
+                    if Arc::ptr_eq(value, &existing) {
+                        *value = merged.clone();
+                    }
+                }
+                for value in self.uri_map.values_mut() {
+                    if Arc::ptr_eq(value, &existing) {
+                        *value = merged.clone();
+                    }
+                }
+
+                match merged.pattern.as_deref().and_then(|p| Regex::new(p).ok()) {
+                    Some(pattern) => {
+                        self.pattern_map.insert(merged.prefix.clone(), pattern);
+                    }
+                    None => {
+                        self.pattern_map.remove(&merged.prefix);
+                    }
+                }
+
+                insert_alias(&mut self.prefix_map, incoming.prefix.clone(), &merged);
+                for synonym in &incoming.prefix_synonyms {
+                    insert_alias(&mut self.prefix_map, synonym.clone(), &merged);
+                }
+                if insert_alias(&mut self.uri_map, incoming.uri_prefix.clone(), &merged) {
+                    self.trie_builder.push(&incoming.uri_prefix);
+                }
+                for synonym in &incoming.uri_prefix_synonyms {
+                    if insert_alias(&mut self.uri_map, synonym.clone(), &merged) {
+                        self.trie_builder.push(synonym);
+                    }
+                }
+            }
+            None => {
+                let rec = Arc::new(incoming.clone());
+                insert_alias(&mut self.prefix_map, rec.prefix.clone(), &rec);
+                if insert_alias(&mut self.uri_map, rec.uri_prefix.clone(), &rec) {
+                    self.trie_builder.push(&rec.uri_prefix);
+                }
+                for prefix in &rec.prefix_synonyms {
+                    insert_alias(&mut self.prefix_map, prefix.clone(), &rec);
+                }
+                for uri_prefix in &rec.uri_prefix_synonyms {
+                    if insert_alias(&mut self.uri_map, uri_prefix.clone(), &rec) {
+                        self.trie_builder.push(uri_prefix);
+                    }
+                }
+                if let Some(pattern) = rec.pattern.as_deref().and_then(|p| Regex::new(p).ok()) {
+                    self.pattern_map.insert(rec.prefix.clone(), pattern);
+                }
+            }
+        }
+    }
 
     /// Find corresponding CURIE `Record` given a prefix
     pub fn find_by_prefix(&self, prefix: &str) -> Option<&Arc<Record>> {
         self.prefix_map.get(prefix)
     }
 
+    /// Enumerate every prefix known to this `Converter`. When `include_synonyms`
+    /// is `false`, only each distinct record's canonical `prefix` is returned;
+    /// when `true`, its `prefix_synonyms` are included too
+    pub fn get_prefixes(&self, include_synonyms: bool) -> HashSet<String> {
+        let mut prefixes = HashSet::new();
+        for record in self.distinct_records() {
+            prefixes.insert(record.prefix.clone());
+            if include_synonyms {
+                prefixes.extend(record.prefix_synonyms.iter().cloned());
+            }
+        }
+        prefixes
+    }
+
+    /// Enumerate every URI prefix known to this `Converter`. When
+    /// `include_synonyms` is `false`, only each distinct record's canonical
+    /// `uri_prefix` is returned; when `true`, its `uri_prefix_synonyms` are
+    /// included too
+    pub fn get_uri_prefixes(&self, include_synonyms: bool) -> HashSet<String> {
+        let mut uri_prefixes = HashSet::new();
+        for record in self.distinct_records() {
+            uri_prefixes.insert(record.uri_prefix.clone());
+            if include_synonyms {
+                uri_prefixes.extend(record.uri_prefix_synonyms.iter().cloned());
+            }
+        }
+        uri_prefixes
+    }
+
+    /// Rewrite the canonical prefix/URI prefix of every record whose current
+    /// canonical prefix/URI prefix is a key in `prefix_remapping`/
+    /// `uri_prefix_remapping`: the mapped value becomes the new canonical
+    /// value, and the old canonical value is demoted into the corresponding
+    /// synonym set. Fails without modifying `self` if the remapping would
+    /// collide two distinct records onto the same canonical prefix or URI
+    /// prefix.
+    pub fn standardize(
+        &mut self,
+        prefix_remapping: HashMap<String, String>,
+        uri_prefix_remapping: HashMap<String, String>,
+    ) -> Result<(), DuplicateRecordError> {
+        let records = self.distinct_records();
+
+        let mut new_records = Vec::with_capacity(records.len());
+        for record in &records {
+            let mut prefix = record.prefix.clone();
+            let mut prefix_synonyms = record.prefix_synonyms.clone();
+            if let Some(new_prefix) = prefix_remapping.get(&record.prefix) {
+                prefix_synonyms.insert(record.prefix.clone());
+                prefix_synonyms.remove(new_prefix);
+                prefix = new_prefix.clone();
+            }
+
+            let mut uri_prefix = record.uri_prefix.clone();
+            let mut uri_prefix_synonyms = record.uri_prefix_synonyms.clone();
+            if let Some(new_uri_prefix) = uri_prefix_remapping.get(&record.uri_prefix) {
+                uri_prefix_synonyms.insert(record.uri_prefix.clone());
+                uri_prefix_synonyms.remove(new_uri_prefix);
+                uri_prefix = new_uri_prefix.clone();
+            }
+
+            new_records.push(Record {
+                prefix,
+                uri_prefix,
+                prefix_synonyms,
+                uri_prefix_synonyms,
+                pattern: record.pattern.clone(),
+            });
+        }
+
+        // Check every surviving key — canonical values AND synonyms — since a
+        // remap target can collide with another record's untouched synonym,
+        // not just its canonical prefix/URI prefix
+        let mut seen_prefixes = HashSet::new();
+        for record in &new_records {
+            for key in std::iter::once(&record.prefix).chain(record.prefix_synonyms.iter()) {
+                if !seen_prefixes.insert(key.clone()) {
+                    return Err(DuplicateRecordError(key.clone()));
+                }
+            }
+        }
+        let mut seen_uri_prefixes = HashSet::new();
+        for record in &new_records {
+            for key in std::iter::once(&record.uri_prefix).chain(record.uri_prefix_synonyms.iter())
+            {
+                if !seen_uri_prefixes.insert(key.clone()) {
+                    return Err(DuplicateRecordError(key.clone()));
+                }
+            }
+        }
+
+        self.prefix_map.clear();
+        self.uri_map.clear();
+        self.pattern_map.clear();
+        self.trie_builder = TrieBuilder::new();
+        for record in new_records {
+            self.insert_record(record)
+                .expect("standardize already validated for prefix/URI prefix collisions");
+        }
+        self.trie = self.trie_builder.build();
+        Ok(())
+    }
+
     /// Find corresponding CURIE `Record` given a URI prefix
     pub fn find_by_uri_prefix(&self, uri_prefix: &str) -> Option<&Arc<Record>> {
         self.uri_map.get(uri_prefix)
@@ -84,29 +610,81 @@ impl Converter {
         self.find_by_uri_prefix(longest_uri)
     }
 
-    /// Compresses a URI to a CURIE
-    pub fn compress(&self, uri: &str) -> Option<String> {
+    /// Parse `curie` into a `Reference` by splitting on the first colon only,
+    /// so identifiers that themselves contain colons (e.g. `obo:GO_0032571`)
+    /// parse correctly. Returns `None` if `curie` contains no colon.
+    pub fn parse_curie(&self, curie: &str) -> Option<Reference> {
+        let (prefix, identifier) = curie.split_once(':')?;
+        Some(Reference {
+            prefix: prefix.to_string(),
+            identifier: identifier.to_string(),
+        })
+    }
+
+    /// Compresses a URI to a `Reference`
+    pub fn compress_to_reference(&self, uri: &str) -> Option<Reference> {
         self.find_by_uri(uri).and_then(|record| {
-            let prefix = &record.prefix;
-            let id = uri.strip_prefix(&record.uri_prefix).or_else(|| {
+            let identifier = uri.strip_prefix(&record.uri_prefix).or_else(|| {
                 record
                     .uri_prefix_synonyms
                     .iter()
                     .find_map(|synonym| uri.strip_prefix(synonym))
             })?;
-            Some(format!("{}:{}", prefix, id))
+            Some(Reference {
+                prefix: record.prefix.clone(),
+                identifier: identifier.to_string(),
+            })
         })
     }
 
+    /// Expands a `Reference` to a URI
+    pub fn expand_reference(&self, reference: &Reference) -> Option<String> {
+        self.find_by_prefix(&reference.prefix)
+            .map(|record| format!("{}{}", record.uri_prefix, reference.identifier))
+    }
+
+    /// Compresses a URI to a CURIE
+    pub fn compress(&self, uri: &str) -> Option<String> {
+        let reference = self.compress_to_reference(uri)?;
+        Some(format!("{}:{}", reference.prefix, reference.identifier))
+    }
+
     /// Expands a CURIE to a URI
     pub fn expand(&self, curie: &str) -> Option<String> {
-        let parts: Vec<&str> = curie.split(':').collect();
-        if parts.len() != 2 {
+        let reference = self.parse_curie(curie)?;
+        self.expand_reference(&reference)
+    }
+
+    /// Like `compress`, but additionally validates the local identifier
+    /// against the record's `pattern` (if any), returning `None` when it
+    /// doesn't match instead of a CURIE for a malformed identifier
+    pub fn compress_checked(&self, uri: &str) -> Option<String> {
+        let reference = self.compress_to_reference(uri)?;
+        if !self.matches_pattern(&reference.prefix, &reference.identifier) {
             return None;
         }
-        let (prefix, id) = (parts[0], parts[1]);
-        self.find_by_prefix(prefix)
-            .map(|record| format!("{}{}", record.uri_prefix, id))
+        Some(format!("{}:{}", reference.prefix, reference.identifier))
+    }
+
+    /// Like `expand`, but additionally validates the local identifier against
+    /// the record's `pattern` (if any), returning `None` when it doesn't match
+    /// instead of a URI for a malformed CURIE
+    pub fn expand_checked(&self, curie: &str) -> Option<String> {
+        let reference = self.parse_curie(curie)?;
+        let record = self.find_by_prefix(&reference.prefix)?;
+        if !self.matches_pattern(&record.prefix, &reference.identifier) {
+            return None;
+        }
+        Some(format!("{}{}", record.uri_prefix, reference.identifier))
+    }
+
+    /// Whether `identifier` satisfies the compiled pattern registered for
+    /// `canonical_prefix`, or `true` if that record has no pattern
+    fn matches_pattern(&self, canonical_prefix: &str, identifier: &str) -> bool {
+        match self.pattern_map.get(canonical_prefix) {
+            Some(pattern) => pattern.is_match(identifier),
+            None => true,
+        }
     }
 }
 
@@ -117,6 +695,27 @@ impl Default for Converter {
     }
 }
 
+/// Record `value` as a synonym of `canonical` unless it already is `canonical`
+fn absorb(value: &str, canonical: &str, synonyms: &mut HashSet<String>) {
+    if value != canonical {
+        synonyms.insert(value.to_string());
+    }
+}
+
+/// Insert `key` -> `record` into `map`, unless `key` already resolves to a
+/// *different* distinct record — in which case leave the existing mapping
+/// alone instead of silently stealing the alias away from whichever record
+/// currently owns it. Returns whether the insertion happened.
+fn insert_alias(map: &mut HashMap<String, Arc<Record>>, key: String, record: &Arc<Record>) -> bool {
+    match map.get(&key) {
+        Some(existing) if !Arc::ptr_eq(existing, record) => false,
+        _ => {
+            map.insert(key, record.clone());
+            true
+        }
+    }
+}
+
 #[test]
 fn main_tests() -> Result<(), Box<dyn std::error::Error>> {
     let mut converter = Converter::new();
@@ -126,12 +725,14 @@ fn main_tests() -> Result<(), Box<dyn std::error::Error>> {
         uri_prefix: "http://purl.obolibrary.org/obo/DOID_".to_string(),
         prefix_synonyms: HashSet::from(["DOID".to_string()]),
         uri_prefix_synonyms: HashSet::from(["https://identifiers.org/DOID/"].map(String::from)),
+        pattern: None,
     };
     let record2 = Record {
         prefix: "obo".to_string(),
         uri_prefix: "http://purl.obolibrary.org/obo/".to_string(),
         prefix_synonyms: HashSet::from(["OBO".to_string()]),
         uri_prefix_synonyms: HashSet::from(["https://identifiers.org/obo/"].map(String::from)),
+        pattern: None,
     };
     converter.add_record(record1)?;
     converter.add_record(record2)?;
@@ -180,19 +781,441 @@ fn main_tests() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[test]
+fn chain_tests() {
+    let mut doid_converter = Converter::new();
+    doid_converter
+        .add_record(Record {
+            prefix: "doid".to_string(),
+            uri_prefix: "http://purl.obolibrary.org/obo/DOID_".to_string(),
+            prefix_synonyms: HashSet::new(),
+            uri_prefix_synonyms: HashSet::new(),
+            pattern: None,
+        })
+        .unwrap();
+
+    // `go` clashes with `GO` below: the first converter should win as primary,
+    // and the second's prefix/URI prefix should be folded in as synonyms
+    let mut go_lowercase_converter = Converter::new();
+    go_lowercase_converter
+        .add_record(Record {
+            prefix: "go".to_string(),
+            uri_prefix: "http://purl.obolibrary.org/obo/GO_".to_string(),
+            prefix_synonyms: HashSet::new(),
+            uri_prefix_synonyms: HashSet::new(),
+            pattern: None,
+        })
+        .unwrap();
+
+    let mut go_uppercase_converter = Converter::new();
+    go_uppercase_converter
+        .add_record(Record {
+            prefix: "GO".to_string(),
+            uri_prefix: "https://identifiers.org/GO/".to_string(),
+            prefix_synonyms: HashSet::new(),
+            uri_prefix_synonyms: HashSet::new(),
+            pattern: None,
+        })
+        .unwrap();
+
+    let merged = Converter::chain(
+        vec![doid_converter, go_lowercase_converter, go_uppercase_converter],
+        true,
+    );
+
+    assert_eq!(merged.find_by_prefix("doid").unwrap().prefix, "doid");
+
+    let go = merged.find_by_prefix("go").unwrap();
+    assert_eq!(go.prefix, "go");
+    assert!(go.prefix_synonyms.contains("GO"));
+    assert!(go
+        .uri_prefix_synonyms
+        .contains("https://identifiers.org/GO/"));
+    assert_eq!(
+        merged.find_by_prefix("GO").unwrap().prefix,
+        "go",
+        "the uppercase alias should resolve to the same merged record"
+    );
+}
+
+#[test]
+fn chain_does_not_steal_conflicting_synonyms() {
+    // `doid` already owns the `DOID` synonym in the first converter
+    let mut first = Converter::new();
+    first
+        .add_record(Record {
+            prefix: "doid".to_string(),
+            uri_prefix: "http://purl.obolibrary.org/obo/DOID_".to_string(),
+            prefix_synonyms: HashSet::from(["DOID".to_string()]),
+            uri_prefix_synonyms: HashSet::new(),
+            pattern: None,
+        })
+        .unwrap();
+
+    // An unrelated, distinct record in the second converter happens to reuse
+    // `DOID` as one of its own synonyms
+    let mut second = Converter::new();
+    second
+        .add_record(Record {
+            prefix: "chebi".to_string(),
+            uri_prefix: "http://purl.obolibrary.org/obo/CHEBI_".to_string(),
+            prefix_synonyms: HashSet::from(["DOID".to_string()]),
+            uri_prefix_synonyms: HashSet::new(),
+            pattern: None,
+        })
+        .unwrap();
+
+    let merged = Converter::chain(vec![first, second], false);
+
+    // `DOID` must still resolve to whichever record claimed it first, never
+    // silently reassigned to the later, unrelated record
+    assert_eq!(merged.find_by_prefix("DOID").unwrap().prefix, "doid");
+    assert_eq!(merged.find_by_prefix("chebi").unwrap().prefix, "chebi");
+}
+
+#[test]
+fn chain_keeps_incoming_pattern_when_existing_has_none() {
+    // The primary converter's `doid` record has no pattern of its own
+    let mut first = Converter::new();
+    first
+        .add_record(Record {
+            prefix: "doid".to_string(),
+            uri_prefix: "http://purl.obolibrary.org/obo/DOID_".to_string(),
+            prefix_synonyms: HashSet::new(),
+            uri_prefix_synonyms: HashSet::new(),
+            pattern: None,
+        })
+        .unwrap();
+
+    // A lower-priority converter's `DOID` record carries a validation pattern
+    let mut second = Converter::new();
+    second
+        .add_record(Record {
+            prefix: "DOID".to_string(),
+            uri_prefix: "http://purl.obolibrary.org/obo/DOID_".to_string(),
+            prefix_synonyms: HashSet::new(),
+            uri_prefix_synonyms: HashSet::new(),
+            pattern: Some(r"^\d+$".to_string()),
+        })
+        .unwrap();
+
+    let merged = Converter::chain(vec![first, second], true);
+
+    // The absorbed pattern must still be enforced, not silently dropped
+    assert!(merged.expand_checked("doid:1234").is_some());
+    assert!(merged.expand_checked("doid:abc").is_none());
+}
+
+#[test]
+fn loader_tests() -> Result<(), Box<dyn std::error::Error>> {
+    let converter = Converter::from_prefix_map(
+        r#"{"doid": "http://purl.obolibrary.org/obo/DOID_"}"#,
+    )?;
+    assert_eq!(
+        converter.expand("doid:1234").unwrap(),
+        "http://purl.obolibrary.org/obo/DOID_1234"
+    );
+
+    let converter = Converter::from_extended_prefix_map(
+        r#"[{
+            "prefix": "doid",
+            "uri_prefix": "http://purl.obolibrary.org/obo/DOID_",
+            "prefix_synonyms": ["DOID"],
+            "uri_prefix_synonyms": ["https://identifiers.org/DOID/"]
+        }]"#,
+    )?;
+    assert_eq!(
+        converter.expand("DOID:1234").unwrap(),
+        "http://purl.obolibrary.org/obo/DOID_1234"
+    );
+
+    let converter = Converter::from_jsonld_context(
+        r#"{
+            "@context": {
+                "doid": "http://purl.obolibrary.org/obo/DOID_",
+                "@vocab": "https://example.org/",
+                "nested": {"@id": "https://example.org/nested"}
+            }
+        }"#,
+    )?;
+    assert_eq!(
+        converter.expand("doid:1234").unwrap(),
+        "http://purl.obolibrary.org/obo/DOID_1234"
+    );
+    assert!(converter.find_by_prefix("nested").is_none());
+
+    Ok(())
+}
+
+#[test]
+fn writer_tests() -> Result<(), Box<dyn std::error::Error>> {
+    let mut converter = Converter::new();
+    converter.add_record(Record {
+        prefix: "doid".to_string(),
+        uri_prefix: "http://purl.obolibrary.org/obo/DOID_".to_string(),
+        prefix_synonyms: HashSet::from(["DOID".to_string()]),
+        uri_prefix_synonyms: HashSet::from(["https://identifiers.org/DOID/".to_string()]),
+        pattern: None,
+    })?;
+
+    let epm = converter.write_extended_prefix_map()?;
+    let roundtripped = Converter::from_extended_prefix_map(&epm)?;
+    assert_eq!(
+        roundtripped.expand("DOID:1234").unwrap(),
+        "http://purl.obolibrary.org/obo/DOID_1234"
+    );
+
+    let context = converter.write_jsonld_context()?;
+    let roundtripped = Converter::from_jsonld_context(&context)?;
+    assert_eq!(
+        roundtripped.expand("doid:1234").unwrap(),
+        "http://purl.obolibrary.org/obo/DOID_1234"
+    );
+    assert_eq!(
+        roundtripped.expand("DOID:1234").unwrap(),
+        "http://purl.obolibrary.org/obo/DOID_1234"
+    );
+
+    let tsv = converter.write_tsv();
+    assert_eq!(
+        tsv,
+        "prefix\turi_prefix\ndoid\thttp://purl.obolibrary.org/obo/DOID_\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn pattern_tests() -> Result<(), Box<dyn std::error::Error>> {
+    let mut converter = Converter::new();
+    converter.add_record(Record {
+        prefix: "doid".to_string(),
+        uri_prefix: "http://purl.obolibrary.org/obo/DOID_".to_string(),
+        prefix_synonyms: HashSet::new(),
+        uri_prefix_synonyms: HashSet::new(),
+        pattern: Some(r"^\d+$".to_string()),
+    })?;
+
+    assert_eq!(
+        converter.expand_checked("doid:1234").unwrap(),
+        "http://purl.obolibrary.org/obo/DOID_1234"
+    );
+    assert!(converter.expand_checked("doid:abc").is_none());
+    // An unchecked expand still succeeds for a malformed identifier
+    assert!(converter.expand("doid:abc").is_some());
+
+    assert_eq!(
+        converter
+            .compress_checked("http://purl.obolibrary.org/obo/DOID_1234")
+            .unwrap(),
+        "doid:1234"
+    );
+    assert!(converter
+        .compress_checked("http://purl.obolibrary.org/obo/DOID_abc")
+        .is_none());
+
+    Ok(())
+}
+
+#[test]
+fn introspection_tests() -> Result<(), Box<dyn std::error::Error>> {
+    let mut converter = Converter::new();
+    converter.add_record(Record {
+        prefix: "doid".to_string(),
+        uri_prefix: "http://purl.obolibrary.org/obo/DOID_".to_string(),
+        prefix_synonyms: HashSet::from(["DOID".to_string()]),
+        uri_prefix_synonyms: HashSet::from(["https://identifiers.org/DOID/".to_string()]),
+        pattern: None,
+    })?;
+
+    assert_eq!(
+        converter.get_prefixes(false),
+        HashSet::from(["doid".to_string()])
+    );
+    assert_eq!(
+        converter.get_prefixes(true),
+        HashSet::from(["doid".to_string(), "DOID".to_string()])
+    );
+
+    assert_eq!(
+        converter.get_uri_prefixes(false),
+        HashSet::from(["http://purl.obolibrary.org/obo/DOID_".to_string()])
+    );
+    assert_eq!(
+        converter.get_uri_prefixes(true),
+        HashSet::from([
+            "http://purl.obolibrary.org/obo/DOID_".to_string(),
+            "https://identifiers.org/DOID/".to_string(),
+        ])
+    );
+
+    Ok(())
+}
+
+#[test]
+fn add_prefix_tests() -> Result<(), Box<dyn std::error::Error>> {
+    let mut converter = Converter::new();
+    converter.add_prefix("doid", "http://purl.obolibrary.org/obo/DOID_")?;
+    assert_eq!(
+        converter.expand("doid:1234").unwrap(),
+        "http://purl.obolibrary.org/obo/DOID_1234"
+    );
+
+    let uri = converter
+        .add_curie("obo", "http://purl.obolibrary.org/obo/", "obo:DOID_1234")?
+        .unwrap();
+    assert_eq!(uri, "http://purl.obolibrary.org/obo/DOID_1234");
+
+    // A sample CURIE that doesn't use any registered prefix doesn't expand
+    assert!(converter
+        .add_curie("chebi", "http://purl.obolibrary.org/obo/CHEBI_", "zzz:nope")?
+        .is_none());
+
+    let mut converter = Converter::new();
+    converter.add_records([
+        Record {
+            prefix: "doid".to_string(),
+            uri_prefix: "http://purl.obolibrary.org/obo/DOID_".to_string(),
+            prefix_synonyms: HashSet::new(),
+            uri_prefix_synonyms: HashSet::new(),
+            pattern: None,
+        },
+        Record {
+            prefix: "obo".to_string(),
+            uri_prefix: "http://purl.obolibrary.org/obo/".to_string(),
+            prefix_synonyms: HashSet::new(),
+            uri_prefix_synonyms: HashSet::new(),
+            pattern: None,
+        },
+    ])?;
+    assert_eq!(
+        converter.expand("doid:1234").unwrap(),
+        "http://purl.obolibrary.org/obo/DOID_1234"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn standardize_tests() -> Result<(), Box<dyn std::error::Error>> {
+    let mut converter = Converter::new();
+    converter.add_record(Record {
+        prefix: "go".to_string(),
+        uri_prefix: "http://purl.obolibrary.org/obo/GO_".to_string(),
+        prefix_synonyms: HashSet::new(),
+        uri_prefix_synonyms: HashSet::new(),
+        pattern: None,
+    })?;
+    converter.add_record(Record {
+        prefix: "doid".to_string(),
+        uri_prefix: "http://purl.obolibrary.org/obo/DOID_".to_string(),
+        prefix_synonyms: HashSet::new(),
+        uri_prefix_synonyms: HashSet::new(),
+        pattern: None,
+    })?;
+
+    converter.standardize(
+        HashMap::from([("go".to_string(), "GO".to_string())]),
+        HashMap::new(),
+    )?;
+
+    let go = converter.find_by_prefix("GO").unwrap();
+    assert_eq!(go.prefix, "GO");
+    assert!(go.prefix_synonyms.contains("go"));
+    assert_eq!(
+        converter.find_by_prefix("go").unwrap().prefix,
+        "GO",
+        "the demoted prefix should still resolve to the same record"
+    );
+
+    // doid is untouched by a remapping that doesn't name its prefix
+    assert_eq!(converter.find_by_prefix("doid").unwrap().prefix, "doid");
+
+    // A remapping that would collide two distinct records is rejected
+    let err = converter
+        .standardize(
+            HashMap::from([("doid".to_string(), "GO".to_string())]),
+            HashMap::new(),
+        )
+        .unwrap_err();
+    assert_eq!(err.0, "GO");
+
+    // A remapping that would collide a new canonical prefix with another
+    // untouched record's existing synonym is also rejected, and leaves the
+    // converter unmodified (rather than panicking or silently corrupting it)
+    let mut converter = Converter::new();
+    converter.add_record(Record {
+        prefix: "go".to_string(),
+        uri_prefix: "http://purl.obolibrary.org/obo/GO_".to_string(),
+        prefix_synonyms: HashSet::new(),
+        uri_prefix_synonyms: HashSet::new(),
+        pattern: None,
+    })?;
+    converter.add_record(Record {
+        prefix: "doid".to_string(),
+        uri_prefix: "http://purl.obolibrary.org/obo/DOID_".to_string(),
+        prefix_synonyms: HashSet::from(["GO".to_string()]),
+        uri_prefix_synonyms: HashSet::new(),
+        pattern: None,
+    })?;
+
+    let err = converter
+        .standardize(
+            HashMap::from([("go".to_string(), "GO".to_string())]),
+            HashMap::new(),
+        )
+        .unwrap_err();
+    assert_eq!(err.0, "GO");
+    assert_eq!(converter.find_by_prefix("go").unwrap().prefix, "go");
+    assert_eq!(converter.find_by_prefix("doid").unwrap().prefix, "doid");
+
+    Ok(())
+}
+
+#[test]
+fn reference_tests() -> Result<(), Box<dyn std::error::Error>> {
+    let mut converter = Converter::new();
+    converter.add_record(Record {
+        prefix: "obo".to_string(),
+        uri_prefix: "http://purl.obolibrary.org/obo/".to_string(),
+        prefix_synonyms: HashSet::new(),
+        uri_prefix_synonyms: HashSet::new(),
+        pattern: None,
+    })?;
+
+    // An identifier containing a colon used to break `expand`'s split(':')
+    let reference = converter.parse_curie("obo:GO_0032571:variant").unwrap();
+    assert_eq!(reference.prefix, "obo");
+    assert_eq!(reference.identifier, "GO_0032571:variant");
+    assert_eq!(
+        reference.as_tuple(),
+        ("obo".to_string(), "GO_0032571:variant".to_string())
+    );
+
+    assert_eq!(
+        converter.expand("obo:GO_0032571:variant").unwrap(),
+        "http://purl.obolibrary.org/obo/GO_0032571:variant"
+    );
+    assert_eq!(
+        converter.expand_reference(&reference).unwrap(),
+        "http://purl.obolibrary.org/obo/GO_0032571:variant"
+    );
+
+    let reference = converter
+        .compress_to_reference("http://purl.obolibrary.org/obo/GO_0032571:variant")
+        .unwrap();
+    assert_eq!(reference.identifier, "GO_0032571:variant");
+    assert_eq!(
+        converter
+            .compress("http://purl.obolibrary.org/obo/GO_0032571:variant")
+            .unwrap(),
+        "obo:GO_0032571:variant"
+    );
+
+    assert!(converter.parse_curie("no-colon-here").is_none());
+
+    Ok(())
+}
+
 // Python API: https://github.com/cthoyt/curies/blob/main/src/curies/api.py#L1099
 // HashSet lookup more efficient than Vec: O(1) vs O(n). But HashSet are not ordered, while Vec are ordered
-
-// /// Stores the prefix and local unique identifier
-// /// for a compact URI (CURIE)
-// pub struct Reference {
-//     prefix: String,
-//     identifier: String,
-// }
-
-// pub struct Record {
-//     curie_prefix: String,
-//     uri_prefix: String,
-//     curie_prefix_synonyms: Vec<String>,
-//     uri_prefix_synonyms: Vec<String>,
-// }